@@ -1,9 +1,11 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Display, Formatter};
 use std::fs::File;
 use std::io::{self, Read, Seek, Write};
 use std::path::Path;
 use std::str::FromStr;
 
+use digest::Digest;
 use itertools::Itertools;
 use log::error;
 use serde::de::{Error as DeserError, Visitor};
@@ -27,6 +29,16 @@ pub enum BundleError {
     Io(#[from] io::Error),
     #[error("unable to write config file: {0}")]
     Ser(#[from] serde_ini::ser::Error),
+    #[error("asset '{0}' referenced by the bundle was not supplied")]
+    MissingAsset(String),
+    #[error("failed to compute integrity digest: {0}")]
+    Integrity(String),
+    #[error("bad JSON config file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("bad TOML config file: {0}")]
+    TomlDe(#[from] toml::de::Error),
+    #[error("unable to write TOML config file: {0}")]
+    TomlSer(#[from] toml::ser::Error),
 }
 
 pub type BundleResult<T> = Result<T, BundleError>;
@@ -38,8 +50,384 @@ pub enum BundleType {
     LauncherOnly,
 }
 
+/// A format a bundle definition can be read from or written to. `Ini` keeps
+/// the crate's ini-specific quirks (stringly bools, semicolon lists);
+/// `Json`/`Toml` use native booleans and arrays instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString)]
+pub enum BundleFormat {
+    Ini,
+    Json,
+    Toml,
+}
+
+impl BundleFormat {
+    /// Guess the format a bundle archive member was written in from its
+    /// file extension, as used by [`BundleConfig::from_archive`].
+    fn from_member_name(name: &str) -> Option<Self> {
+        match name.rsplit_once('.')?.1 {
+            "ini" => Some(BundleFormat::Ini),
+            "json" => Some(BundleFormat::Json),
+            "toml" => Some(BundleFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// Target OS for a [`LaunchEntry`].
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, DeserializeFromStr, SerializeDisplay,
+)]
+pub enum Platform {
+    Linux,
+    Mac,
+    Windows,
+    Unknown,
+}
+
+/// A single way to launch a bundle, optionally restricted to one platform.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LaunchEntry {
+    pub platform: Option<Platform>,
+    pub executable: String,
+    pub arguments: Vec<String>,
+}
+
+impl LaunchEntry {
+    pub fn new(platform: Option<Platform>, executable: String, arguments: Vec<String>) -> Self {
+        Self {
+            platform,
+            executable,
+            arguments,
+        }
+    }
+}
+
+/// Characters with meaning in the keyfile list/record codecs below, escaped
+/// by [`escape_token`] in the text fields of [`LaunchEntry`] and
+/// [`IntegrityEntry`] records. Plain string list items such as
+/// `LauncherTags` and `Winetricks` are written without escaping.
+const KEYFILE_SPECIALS: &str = "|,;";
+
+/// Backslash-escape any of `specials` (and `\` itself) in `s`, so it can be
+/// embedded in a `|`- or `,`-delimited record without being mistaken for a
+/// delimiter.
+fn escape_token(s: &str, specials: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == '\\' || specials.contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Reverse of [`escape_token`].
+fn unescape_token(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Split `s` on `delim`, treating a `delim` preceded by an unescaped `\` as
+/// literal. Pieces are returned still escaped, for further splitting or for
+/// [`unescape_token`].
+fn split_unescaped(s: &str, delim: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            current.push('\\');
+            if let Some(next) = chars.next() {
+                current.push(next);
+            }
+        } else if c == delim {
+            parts.push(current);
+            current = String::new();
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+impl Display for LaunchEntry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}",
+            self.platform.map_or(String::new(), |p| p.to_string()),
+            escape_token(&self.executable, KEYFILE_SPECIALS),
+            Itertools::intersperse(
+                self.arguments
+                    .iter()
+                    .map(|arg| escape_token(arg, KEYFILE_SPECIALS)),
+                ",".to_string()
+            )
+            .collect::<String>()
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("'{0}' is not a valid launch entry")]
+pub struct LaunchEntryParseError(String);
+
+impl FromStr for LaunchEntry {
+    type Err = LaunchEntryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || LaunchEntryParseError(s.to_string());
+        let mut parts = split_unescaped(s, '|');
+        if parts.len() != 3 {
+            return Err(err());
+        }
+        let arguments = parts.pop().unwrap();
+        let executable = parts.pop().unwrap();
+        let platform = parts.pop().unwrap();
+
+        let platform = if platform.is_empty() {
+            None
+        } else {
+            Some(Platform::from_str(&platform).map_err(|_| err())?)
+        };
+
+        let arguments = if arguments.is_empty() {
+            Vec::new()
+        } else {
+            split_unescaped(&arguments, ',')
+                .iter()
+                .map(|arg| unescape_token(arg))
+                .collect()
+        };
+
+        Ok(LaunchEntry {
+            platform,
+            executable: unescape_token(&executable),
+            arguments,
+        })
+    }
+}
+
+/// Characters with meaning in [`EnvVar`]'s `KEY=VALUE` codec: the usual
+/// [`KEYFILE_SPECIALS`] plus `=`, the delimiter `EnvVar` itself introduces
+/// between key and value.
+const ENV_VAR_SPECIALS: &str = "|,;=";
+
+/// A single `KEY=VALUE` environment variable, as set for a compatibility
+/// runner.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+impl Display for EnvVar {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}={}",
+            escape_token(&self.key, ENV_VAR_SPECIALS),
+            escape_token(&self.value, ENV_VAR_SPECIALS)
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("'{0}' is not a valid KEY=VALUE environment variable")]
+pub struct EnvVarParseError(String);
+
+impl FromStr for EnvVar {
+    type Err = EnvVarParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || EnvVarParseError(s.to_string());
+        let mut parts = split_unescaped(s, '=');
+        if parts.len() != 2 {
+            return Err(err());
+        }
+        let value = parts.pop().unwrap();
+        let key = parts.pop().unwrap();
+        Ok(EnvVar {
+            key: unescape_token(&key),
+            value: unescape_token(&value),
+        })
+    }
+}
+
+/// Wine/Proton compatibility settings for a bundle whose `exec` is a
+/// Windows executable. Read back out of a [`Bundle`] via
+/// [`Bundle::compatibility`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Compatibility {
+    pub runner: Option<String>,
+    pub runner_version: Option<String>,
+    pub dxvk_version: Option<String>,
+    pub winetricks: Vec<String>,
+    pub env: Vec<EnvVar>,
+}
+
+impl Compatibility {
+    /// The environment a launcher should set before starting this bundle's
+    /// executable through the compatibility layer.
+    pub fn environment(&self) -> Vec<(String, String)> {
+        self.env
+            .iter()
+            .map(|var| (var.key.clone(), var.value.clone()))
+            .collect()
+    }
+}
+
+/// Digest algorithm for an [`IntegrityEntry`].
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, DeserializeFromStr, SerializeDisplay,
+)]
+pub enum HashAlgo {
+    Md5,
+    Sha256,
+}
+
+impl HashAlgo {
+    fn digest_len(self) -> usize {
+        match self {
+            HashAlgo::Md5 => 32,
+            HashAlgo::Sha256 => 64,
+        }
+    }
+
+    fn digest(self, read: &mut impl Read) -> io::Result<String> {
+        Ok(match self {
+            HashAlgo::Md5 => {
+                let mut hasher = md5::Md5::new();
+                io::copy(read, &mut hasher)?;
+                format!("{:x}", hasher.finalize())
+            }
+            HashAlgo::Sha256 => {
+                let mut hasher = sha2::Sha256::new();
+                io::copy(read, &mut hasher)?;
+                format!("{:x}", hasher.finalize())
+            }
+        })
+    }
+}
+
+/// A recorded digest for one file inside a bundle archive, used to detect
+/// corruption or tampering before a consumer trusts the archive.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct IntegrityEntry {
+    pub path: String,
+    pub algorithm: HashAlgo,
+    pub digest: String,
+    pub size: u64,
+}
+
+impl Display for IntegrityEntry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}",
+            escape_token(&self.path, KEYFILE_SPECIALS),
+            self.algorithm,
+            self.digest,
+            self.size
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("'{0}' is not a valid integrity entry")]
+pub struct IntegrityEntryParseError(String);
+
+impl FromStr for IntegrityEntry {
+    type Err = IntegrityEntryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || IntegrityEntryParseError(s.to_string());
+        let mut parts = split_unescaped(s, '|');
+        if parts.len() != 4 {
+            return Err(err());
+        }
+        let size = parts.pop().unwrap().parse().map_err(|_| err())?;
+        let digest = parts.pop().unwrap();
+        let algorithm = HashAlgo::from_str(&parts.pop().unwrap()).map_err(|_| err())?;
+        let path = unescape_token(&parts.pop().unwrap());
+
+        Ok(IntegrityEntry {
+            path,
+            algorithm,
+            digest,
+            size,
+        })
+    }
+}
+
+/// A file that failed to verify against its [`IntegrityEntry`]: either
+/// missing from the archive entirely, or present but disagreeing on size
+/// or digest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntegrityMismatch {
+    Missing {
+        path: String,
+    },
+    SizeMismatch {
+        path: String,
+        expected: u64,
+        actual: u64,
+    },
+    DigestMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// The `[Bundle]` ini keys this crate understands, i.e. every `rename`d or
+/// `PascalCase`d field name on [`Bundle`]. Used by
+/// [`BundleConfig::to_write_preserving`] to tell a key it doesn't recognize
+/// (kept verbatim) apart from one it does recognize but whose value is
+/// currently unset (dropped).
+const KNOWN_BUNDLE_KEYS: &[&str] = &[
+    "Name",
+    "Type",
+    "StoreID",
+    "HomebrewID",
+    "Exec",
+    "EncryptedImage",
+    "Version",
+    "Background",
+    "PreferXBoxMode",
+    "Launcher",
+    "LauncherTags",
+    "LauncherExec",
+    "LaunchEntries",
+    "Runner",
+    "RunnerVersion",
+    "DxvkVersion",
+    "Winetricks",
+    "Env",
+    "Integrity",
+];
+
+// Deliberately not `deny_unknown_fields`: `from_read_preserving` needs to
+// round-trip a `bundle.ini` containing keys from a newer version of this
+// crate, not hard-error on them. `to_write_preserving` retains those
+// unrecognized keys verbatim via `KNOWN_BUNDLE_KEYS`.
 #[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+#[serde(rename_all = "PascalCase")]
 pub struct Bundle {
     pub name: String,
     #[serde(rename = "Type")]
@@ -81,6 +469,491 @@ pub struct Bundle {
     pub launcher_tags: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub launcher_exec: Option<String>,
+
+    #[serde(
+        rename = "LaunchEntries",
+        default,
+        deserialize_with = "de_keyfile_list",
+        serialize_with = "ser_keyfile_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub launch_entries: Vec<LaunchEntry>,
+
+    #[serde(rename = "Runner", skip_serializing_if = "Option::is_none")]
+    pub runner: Option<String>,
+    #[serde(rename = "RunnerVersion", skip_serializing_if = "Option::is_none")]
+    pub runner_version: Option<String>,
+    #[serde(rename = "DxvkVersion", skip_serializing_if = "Option::is_none")]
+    pub dxvk_version: Option<String>,
+    #[serde(
+        rename = "Winetricks",
+        default,
+        deserialize_with = "de_keyfile_list",
+        serialize_with = "ser_keyfile_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub winetricks: Vec<String>,
+    #[serde(
+        rename = "Env",
+        default,
+        deserialize_with = "de_keyfile_list",
+        serialize_with = "ser_keyfile_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub env: Vec<EnvVar>,
+
+    #[serde(
+        rename = "Integrity",
+        default,
+        deserialize_with = "de_keyfile_list",
+        serialize_with = "ser_keyfile_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub integrity: Vec<IntegrityEntry>,
+}
+
+impl Bundle {
+    /// This bundle's wine/proton compatibility settings, if any are set.
+    pub fn compatibility(&self) -> Compatibility {
+        Compatibility {
+            runner: self.runner.clone(),
+            runner_version: self.runner_version.clone(),
+            dxvk_version: self.dxvk_version.clone(),
+            winetricks: self.winetricks.clone(),
+            env: self.env.clone(),
+        }
+    }
+
+    /// Names of the resource files this bundle's config points at, as
+    /// opposed to the `bundle.ini`/`bundle.json` etc describing it.
+    fn referenced_assets(&self) -> impl Iterator<Item = &str> {
+        [&self.encrypted_image, &self.exec, &self.launcher_exec]
+            .into_iter()
+            .filter_map(|asset| asset.as_deref())
+            .chain(
+                self.launch_entries
+                    .iter()
+                    .map(|entry| entry.executable.as_str()),
+            )
+    }
+
+    /// Record another way to launch this bundle.
+    pub fn add_launch(
+        &mut self,
+        platform: Option<Platform>,
+        executable: String,
+        arguments: Vec<String>,
+    ) -> &mut Self {
+        self.launch_entries
+            .push(LaunchEntry::new(platform, executable, arguments));
+        self
+    }
+
+    /// Find the best [`LaunchEntry`] for `for_platform`, falling back to an
+    /// unplatformed entry and then the legacy `Exec=` field.
+    pub fn resolve_exec(&self, for_platform: Platform) -> Option<LaunchEntry> {
+        self.launch_entries
+            .iter()
+            .find(|entry| entry.platform == Some(for_platform))
+            .or_else(|| {
+                self.launch_entries
+                    .iter()
+                    .find(|entry| entry.platform.is_none())
+            })
+            .cloned()
+            .or_else(|| {
+                self.exec.clone().map(|executable| LaunchEntry {
+                    platform: None,
+                    executable,
+                    arguments: Vec::new(),
+                })
+            })
+    }
+}
+
+/// How a [`BundleOverride`]'s list fields combine with the base bundle's.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Display,
+    EnumString,
+    DeserializeFromStr,
+    SerializeDisplay,
+)]
+pub enum ListMergePolicy {
+    #[default]
+    Replace,
+    Append,
+}
+
+/// A sparse set of changes to apply on top of a base [`Bundle`]. Every field
+/// is optional: only the fields present are touched by [`BundleOverride::apply`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BundleOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "Type", skip_serializing_if = "Option::is_none")]
+    pub bundle_type: Option<BundleType>,
+    #[serde(rename = "StoreID", skip_serializing_if = "Option::is_none")]
+    pub store_id: Option<String>,
+    #[serde(rename = "HomebrewID", skip_serializing_if = "Option::is_none")]
+    pub homebrew_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encrypted_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<bool>,
+    #[serde(rename = "PreferXBoxMode", skip_serializing_if = "Option::is_none")]
+    pub prefer_xbox_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launcher: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "de_keyfile_list_opt",
+        serialize_with = "ser_keyfile_list_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub launcher_tags: Option<Vec<String>>,
+    #[serde(rename = "LauncherTagsPolicy", default)]
+    pub launcher_tags_policy: ListMergePolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launcher_exec: Option<String>,
+    #[serde(
+        rename = "LaunchEntries",
+        default,
+        deserialize_with = "de_keyfile_list_opt",
+        serialize_with = "ser_keyfile_list_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub launch_entries: Option<Vec<LaunchEntry>>,
+    #[serde(rename = "LaunchEntriesPolicy", default)]
+    pub launch_entries_policy: ListMergePolicy,
+    #[serde(rename = "Runner", skip_serializing_if = "Option::is_none")]
+    pub runner: Option<String>,
+    #[serde(rename = "RunnerVersion", skip_serializing_if = "Option::is_none")]
+    pub runner_version: Option<String>,
+    #[serde(rename = "DxvkVersion", skip_serializing_if = "Option::is_none")]
+    pub dxvk_version: Option<String>,
+    #[serde(
+        rename = "Winetricks",
+        default,
+        deserialize_with = "de_keyfile_list_opt",
+        serialize_with = "ser_keyfile_list_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub winetricks: Option<Vec<String>>,
+    #[serde(rename = "WinetricksPolicy", default)]
+    pub winetricks_policy: ListMergePolicy,
+    #[serde(
+        rename = "Env",
+        default,
+        deserialize_with = "de_keyfile_list_opt",
+        serialize_with = "ser_keyfile_list_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub env: Option<Vec<EnvVar>>,
+    #[serde(rename = "EnvPolicy", default)]
+    pub env_policy: ListMergePolicy,
+    #[serde(
+        rename = "Integrity",
+        default,
+        deserialize_with = "de_keyfile_list_opt",
+        serialize_with = "ser_keyfile_list_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub integrity: Option<Vec<IntegrityEntry>>,
+    #[serde(rename = "IntegrityPolicy", default)]
+    pub integrity_policy: ListMergePolicy,
+}
+
+fn merge_list<T: Clone>(base: &mut Vec<T>, overlay: &[T], policy: ListMergePolicy) {
+    match policy {
+        ListMergePolicy::Replace => *base = overlay.to_vec(),
+        ListMergePolicy::Append => base.extend(overlay.iter().cloned()),
+    }
+}
+
+impl BundleOverride {
+    /// Overwrite only the fields present in this override onto `base`.
+    pub fn apply(&self, base: &mut Bundle) {
+        if let Some(name) = &self.name {
+            base.name = name.clone();
+        }
+        if let Some(bundle_type) = self.bundle_type {
+            base.bundle_type = bundle_type;
+        }
+        if let Some(store_id) = &self.store_id {
+            base.store_id = Some(store_id.clone());
+        }
+        if let Some(homebrew_id) = &self.homebrew_id {
+            base.homebrew_id = Some(homebrew_id.clone());
+        }
+        if let Some(exec) = &self.exec {
+            base.exec = Some(exec.clone());
+        }
+        if let Some(encrypted_image) = &self.encrypted_image {
+            base.encrypted_image = Some(encrypted_image.clone());
+        }
+        if let Some(version) = &self.version {
+            base.version = Some(version.clone());
+        }
+        if let Some(background) = self.background {
+            base.background = background;
+        }
+        if let Some(prefer_xbox_mode) = self.prefer_xbox_mode {
+            base.prefer_xbox_mode = prefer_xbox_mode;
+        }
+        if let Some(launcher) = &self.launcher {
+            base.launcher = Some(launcher.clone());
+        }
+        if let Some(launcher_tags) = &self.launcher_tags {
+            merge_list(
+                &mut base.launcher_tags,
+                launcher_tags,
+                self.launcher_tags_policy,
+            );
+        }
+        if let Some(launcher_exec) = &self.launcher_exec {
+            base.launcher_exec = Some(launcher_exec.clone());
+        }
+        if let Some(launch_entries) = &self.launch_entries {
+            merge_list(
+                &mut base.launch_entries,
+                launch_entries,
+                self.launch_entries_policy,
+            );
+        }
+        if let Some(runner) = &self.runner {
+            base.runner = Some(runner.clone());
+        }
+        if let Some(runner_version) = &self.runner_version {
+            base.runner_version = Some(runner_version.clone());
+        }
+        if let Some(dxvk_version) = &self.dxvk_version {
+            base.dxvk_version = Some(dxvk_version.clone());
+        }
+        if let Some(winetricks) = &self.winetricks {
+            merge_list(&mut base.winetricks, winetricks, self.winetricks_policy);
+        }
+        if let Some(env) = &self.env {
+            merge_list(&mut base.env, env, self.env_policy);
+        }
+        if let Some(integrity) = &self.integrity {
+            merge_list(&mut base.integrity, integrity, self.integrity_policy);
+        }
+    }
+
+    fn from_read<R: Read>(read: R) -> BundleResult<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct BundleOverrideConfig {
+            bundle: BundleOverride,
+        }
+
+        let config: BundleOverrideConfig = serde_ini::from_read(read)?;
+        Ok(config.bundle)
+    }
+}
+
+/// One line of a parsed ini document, format-preserving enough to be
+/// re-emitted byte-for-byte when nothing about it changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IniToken {
+    Comment(String),
+    Blank,
+    Section(String),
+    KeyValue {
+        key: String,
+        value: String,
+        /// The line's original indentation and `key`/`value` separator
+        /// (including the `=`), encoded as `"{indent}\0{separator}"` so
+        /// [`IniToken::render`] can rebuild the exact original formatting
+        /// around a (possibly updated) value.
+        raw_whitespace: String,
+    },
+}
+
+impl IniToken {
+    fn render(&self) -> String {
+        match self {
+            IniToken::Comment(line) => line.clone(),
+            IniToken::Blank => String::new(),
+            IniToken::Section(name) => format!("[{name}]"),
+            IniToken::KeyValue {
+                key,
+                value,
+                raw_whitespace,
+            } => {
+                let mut parts = raw_whitespace.splitn(2, '\0');
+                let indent = parts.next().unwrap_or("");
+                let separator = parts.next().unwrap_or("=");
+                format!("{indent}{key}{separator}{value}")
+            }
+        }
+    }
+}
+
+fn tokenize_ini(input: &str) -> Vec<IniToken> {
+    input
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                IniToken::Blank
+            } else if trimmed.starts_with(';') || trimmed.starts_with('#') {
+                IniToken::Comment(line.to_string())
+            } else if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                IniToken::Section(trimmed[1..trimmed.len() - 1].to_string())
+            } else if let Some(eq) = line.find('=') {
+                let indent_len = line.len() - line.trim_start().len();
+                let key = line[indent_len..eq].trim_end().to_string();
+                let after_eq = &line[eq + 1..];
+                let value = after_eq.trim_start().to_string();
+                let separator_end = eq + 1 + (after_eq.len() - value.len());
+                IniToken::KeyValue {
+                    raw_whitespace: format!(
+                        "{}\0{}",
+                        &line[..indent_len],
+                        &line[indent_len + key.len()..separator_end]
+                    ),
+                    key,
+                    value,
+                }
+            } else {
+                IniToken::Comment(line.to_string())
+            }
+        })
+        .collect()
+}
+
+/// Mirrors [`Bundle`] field-for-field, using native bool/array types instead
+/// of the ini-only stringly-bool and semicolon-list codecs.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct NativeBundle {
+    name: String,
+    #[serde(rename = "Type")]
+    bundle_type: BundleType,
+    #[serde(rename = "StoreID", skip_serializing_if = "Option::is_none")]
+    store_id: Option<String>,
+    #[serde(rename = "HomebrewID", skip_serializing_if = "Option::is_none")]
+    homebrew_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encrypted_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    background: bool,
+    #[serde(rename = "PreferXBoxMode", default, skip_serializing_if = "is_false")]
+    prefer_xbox_mode: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    launcher: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    launcher_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    launcher_exec: Option<String>,
+    #[serde(
+        rename = "LaunchEntries",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    launch_entries: Vec<LaunchEntry>,
+    #[serde(rename = "Runner", skip_serializing_if = "Option::is_none")]
+    runner: Option<String>,
+    #[serde(rename = "RunnerVersion", skip_serializing_if = "Option::is_none")]
+    runner_version: Option<String>,
+    #[serde(rename = "DxvkVersion", skip_serializing_if = "Option::is_none")]
+    dxvk_version: Option<String>,
+    #[serde(rename = "Winetricks", default, skip_serializing_if = "Vec::is_empty")]
+    winetricks: Vec<String>,
+    #[serde(rename = "Env", default, skip_serializing_if = "Vec::is_empty")]
+    env: Vec<EnvVar>,
+    #[serde(rename = "Integrity", default, skip_serializing_if = "Vec::is_empty")]
+    integrity: Vec<IntegrityEntry>,
+}
+
+impl From<&Bundle> for NativeBundle {
+    fn from(bundle: &Bundle) -> Self {
+        NativeBundle {
+            name: bundle.name.clone(),
+            bundle_type: bundle.bundle_type,
+            store_id: bundle.store_id.clone(),
+            homebrew_id: bundle.homebrew_id.clone(),
+            exec: bundle.exec.clone(),
+            encrypted_image: bundle.encrypted_image.clone(),
+            version: bundle.version.clone(),
+            background: bundle.background,
+            prefer_xbox_mode: bundle.prefer_xbox_mode,
+            launcher: bundle.launcher.clone(),
+            launcher_tags: bundle.launcher_tags.clone(),
+            launcher_exec: bundle.launcher_exec.clone(),
+            launch_entries: bundle.launch_entries.clone(),
+            runner: bundle.runner.clone(),
+            runner_version: bundle.runner_version.clone(),
+            dxvk_version: bundle.dxvk_version.clone(),
+            winetricks: bundle.winetricks.clone(),
+            env: bundle.env.clone(),
+            integrity: bundle.integrity.clone(),
+        }
+    }
+}
+
+impl From<NativeBundle> for Bundle {
+    fn from(native: NativeBundle) -> Self {
+        Bundle {
+            name: native.name,
+            bundle_type: native.bundle_type,
+            store_id: native.store_id,
+            homebrew_id: native.homebrew_id,
+            exec: native.exec,
+            encrypted_image: native.encrypted_image,
+            version: native.version,
+            background: native.background,
+            prefer_xbox_mode: native.prefer_xbox_mode,
+            launcher: native.launcher,
+            launcher_tags: native.launcher_tags,
+            launcher_exec: native.launcher_exec,
+            launch_entries: native.launch_entries,
+            runner: native.runner,
+            runner_version: native.runner_version,
+            dxvk_version: native.dxvk_version,
+            winetricks: native.winetricks,
+            env: native.env,
+            integrity: native.integrity,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct NativeBundleConfig {
+    bundle: NativeBundle,
+}
+
+impl From<&BundleConfig> for NativeBundleConfig {
+    fn from(config: &BundleConfig) -> Self {
+        NativeBundleConfig {
+            bundle: (&config.bundle).into(),
+        }
+    }
+}
+
+impl From<NativeBundleConfig> for BundleConfig {
+    fn from(native: NativeBundleConfig) -> Self {
+        BundleConfig {
+            bundle: native.bundle.into(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -160,15 +1033,15 @@ where
         where
             E: DeserError,
         {
-            let mut v = s.split(';').collect::<Vec<_>>();
+            let mut v = split_unescaped(s, ';');
             if let Some(tail) = v.last() {
                 if tail.is_empty() {
                     v.pop();
                 }
             }
 
-            v.into_iter()
-                .map(FromStr::from_str)
+            v.iter()
+                .map(|part| T::from_str(part))
                 .collect::<Result<Vec<_>, <T as FromStr>::Err>>()
                 .map_err(DeserError::custom)
         }
@@ -177,6 +1050,29 @@ where
     deserializer.deserialize_str(SemicolonSeparatedVisitor(Default::default()))
 }
 
+// Like `ser_keyfile_list`/`de_keyfile_list`, but for `Option<Vec<T>>` fields
+// on `BundleOverride`, where absence (not just emptiness) is meaningful:
+// `None` means "don't touch this list", an empty list means "clear it".
+fn ser_keyfile_list_opt<T, S>(value: &Option<Vec<T>>, ser: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    match value {
+        Some(v) => ser_keyfile_list(v, ser),
+        None => ser.serialize_none(),
+    }
+}
+
+fn de_keyfile_list_opt<'de, T, D>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+    D: Deserializer<'de>,
+{
+    de_keyfile_list(deserializer).map(Some)
+}
+
 pub struct BundleConfigBuilder {
     name: String,
     bundle_type: BundleType,
@@ -196,6 +1092,12 @@ impl BundleConfigBuilder {
             launcher: None,
             prefer_xbox_mode: false,
             version: None,
+            launch_entries: Vec::new(),
+            runner: None,
+            runner_version: None,
+            dxvk_version: None,
+            winetricks: Vec::new(),
+            env: Vec::new(),
         }
     }
 
@@ -212,6 +1114,12 @@ impl BundleConfigBuilder {
             prefer_xbox_mode: false,
             version: None,
             encrypted_image: None,
+            launch_entries: Vec::new(),
+            runner: None,
+            runner_version: None,
+            dxvk_version: None,
+            winetricks: Vec::new(),
+            env: Vec::new(),
         }
     }
 }
@@ -225,9 +1133,47 @@ pub struct HomebrewBundleConfigBuilder {
     version: Option<String>,
     prefer_xbox_mode: bool,
     launcher: Option<String>,
+    launch_entries: Vec<LaunchEntry>,
+    runner: Option<String>,
+    runner_version: Option<String>,
+    dxvk_version: Option<String>,
+    winetricks: Vec<String>,
+    env: Vec<EnvVar>,
 }
 
 impl HomebrewBundleConfigBuilder {
+    pub fn add_launch(
+        mut self,
+        platform: Option<Platform>,
+        executable: String,
+        arguments: Vec<String>,
+    ) -> Self {
+        self.launch_entries
+            .push(LaunchEntry::new(platform, executable, arguments));
+        self
+    }
+
+    pub fn with_runner(mut self, runner: String, runner_version: Option<String>) -> Self {
+        self.runner = Some(runner);
+        self.runner_version = runner_version;
+        self
+    }
+
+    pub fn with_dxvk(mut self, dxvk_version: String) -> Self {
+        self.dxvk_version = Some(dxvk_version);
+        self
+    }
+
+    pub fn winetricks(mut self, winetricks: Vec<String>) -> Self {
+        self.winetricks = winetricks;
+        self
+    }
+
+    pub fn env_var(mut self, key: String, value: String) -> Self {
+        self.env.push(EnvVar { key, value });
+        self
+    }
+
     pub fn version(mut self, version: String) -> Self {
         self.version = Some(version);
         self
@@ -283,6 +1229,13 @@ impl HomebrewBundleConfigBuilder {
                 launcher_tags: Vec::new(),
                 launcher_exec: None,
                 encrypted_image: None,
+                launch_entries: self.launch_entries,
+                runner: self.runner,
+                runner_version: self.runner_version,
+                dxvk_version: self.dxvk_version,
+                winetricks: self.winetricks,
+                env: self.env,
+                integrity: Vec::new(),
             },
         }
     }
@@ -301,9 +1254,47 @@ pub struct StoreBundleConfigBuilder {
     launcher_tags: Vec<String>,
     launcher_exec: Option<String>,
     encrypted_image: Option<String>,
+    launch_entries: Vec<LaunchEntry>,
+    runner: Option<String>,
+    runner_version: Option<String>,
+    dxvk_version: Option<String>,
+    winetricks: Vec<String>,
+    env: Vec<EnvVar>,
 }
 
 impl StoreBundleConfigBuilder {
+    pub fn add_launch(
+        mut self,
+        platform: Option<Platform>,
+        executable: String,
+        arguments: Vec<String>,
+    ) -> Self {
+        self.launch_entries
+            .push(LaunchEntry::new(platform, executable, arguments));
+        self
+    }
+
+    pub fn with_runner(mut self, runner: String, runner_version: Option<String>) -> Self {
+        self.runner = Some(runner);
+        self.runner_version = runner_version;
+        self
+    }
+
+    pub fn with_dxvk(mut self, dxvk_version: String) -> Self {
+        self.dxvk_version = Some(dxvk_version);
+        self
+    }
+
+    pub fn winetricks(mut self, winetricks: Vec<String>) -> Self {
+        self.winetricks = winetricks;
+        self
+    }
+
+    pub fn env_var(mut self, key: String, value: String) -> Self {
+        self.env.push(EnvVar { key, value });
+        self
+    }
+
     pub fn version(mut self, version: String) -> Self {
         self.version = Some(version);
         self
@@ -389,6 +1380,13 @@ impl StoreBundleConfigBuilder {
                 launcher_tags: self.launcher_tags,
                 launcher_exec: self.launcher_exec,
                 encrypted_image: self.encrypted_image,
+                launch_entries: self.launch_entries,
+                runner: self.runner,
+                runner_version: self.runner_version,
+                dxvk_version: self.dxvk_version,
+                winetricks: self.winetricks,
+                env: self.env,
+                integrity: Vec::new(),
             },
         }
     }
@@ -407,15 +1405,193 @@ impl BundleConfig {
         Ok(serde_ini::to_writer(write, self)?)
     }
 
+    /// Like [`BundleConfig::from_read`], but also returns the source text
+    /// broken into [`IniToken`]s so it can later be handed to
+    /// [`BundleConfig::to_write_preserving`] to save the file back without
+    /// disturbing anything this crate doesn't understand.
+    pub fn from_read_preserving<R: Read>(mut read: R) -> BundleResult<(Self, Vec<IniToken>)> {
+        let mut text = String::new();
+        read.read_to_string(&mut text)?;
+        let tokens = tokenize_ini(&text);
+        let config = Self::from_read(text.as_bytes())?;
+        Ok((config, tokens))
+    }
+
+    /// Re-emit `tokens` with this config's current values, changing only
+    /// the lines whose value actually differs and appending any field that
+    /// wasn't present in `tokens` to the end of the `[Bundle]` section.
+    /// Comments, blank lines, key order, and unknown keys all pass through
+    /// untouched.
+    pub fn to_write_preserving<W: Write>(
+        &self,
+        tokens: &[IniToken],
+        mut write: W,
+    ) -> BundleResult<()> {
+        let mut canonical = Vec::new();
+        self.to_write(&mut canonical)?;
+        let canonical_tokens = tokenize_ini(&String::from_utf8_lossy(&canonical));
+
+        let mut canonical_order = Vec::new();
+        let mut canonical_values = HashMap::new();
+        for token in &canonical_tokens {
+            if let IniToken::KeyValue {
+                key,
+                value,
+                raw_whitespace,
+            } = token
+            {
+                canonical_order.push(key.clone());
+                canonical_values.insert(key.clone(), (value.clone(), raw_whitespace.clone()));
+            }
+        }
+
+        let has_bundle_section = tokens
+            .iter()
+            .any(|t| matches!(t, IniToken::Section(name) if name.eq_ignore_ascii_case("Bundle")));
+
+        let mut emitted = HashSet::new();
+        let mut in_bundle_section = false;
+        let mut bundle_section_end = None;
+        let mut lines = Vec::new();
+        for token in tokens {
+            match token {
+                IniToken::Section(name) => {
+                    if in_bundle_section && bundle_section_end.is_none() {
+                        bundle_section_end = Some(lines.len());
+                    }
+                    in_bundle_section = name.eq_ignore_ascii_case("Bundle");
+                    lines.push(token.render());
+                }
+                IniToken::KeyValue {
+                    key,
+                    raw_whitespace,
+                    ..
+                } if in_bundle_section && canonical_values.contains_key(key) => {
+                    let (value, _) = &canonical_values[key];
+                    lines.push(
+                        IniToken::KeyValue {
+                            key: key.clone(),
+                            value: value.clone(),
+                            raw_whitespace: raw_whitespace.clone(),
+                        }
+                        .render(),
+                    );
+                    emitted.insert(key.clone());
+                }
+                // A known field that's still in the `[Bundle]` section but no
+                // longer has a canonical value (e.g. an `Option` field set
+                // back to `None`) is dropped rather than re-emitted with its
+                // stale original value.
+                IniToken::KeyValue { key, .. }
+                    if in_bundle_section && KNOWN_BUNDLE_KEYS.contains(&key.as_str()) => {}
+                // A key this crate doesn't recognize at all (e.g. written by
+                // a newer version) passes through untouched.
+                other => lines.push(other.render()),
+            }
+        }
+
+        if !has_bundle_section {
+            lines.push(IniToken::Section("Bundle".to_string()).render());
+        }
+        let mut appended = Vec::new();
+        for key in canonical_order {
+            if !emitted.contains(&key) {
+                let (value, raw_whitespace) = canonical_values[&key].clone();
+                appended.push(
+                    IniToken::KeyValue {
+                        key,
+                        value,
+                        raw_whitespace,
+                    }
+                    .render(),
+                );
+            }
+        }
+        let insert_at = bundle_section_end.unwrap_or(lines.len());
+        lines.splice(insert_at..insert_at, appended);
+
+        for line in lines {
+            writeln!(write, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// Read a bundle definition in a specific [`BundleFormat`].
+    pub fn from_read_with<R: Read>(mut read: R, format: BundleFormat) -> BundleResult<Self> {
+        match format {
+            BundleFormat::Ini => Self::from_read(read),
+            BundleFormat::Json => {
+                let native: NativeBundleConfig = serde_json::from_reader(read)?;
+                Ok(native.into())
+            }
+            BundleFormat::Toml => {
+                let mut text = String::new();
+                read.read_to_string(&mut text)?;
+                let native: NativeBundleConfig = toml::from_str(&text)?;
+                Ok(native.into())
+            }
+        }
+    }
+
+    /// Write a bundle definition in a specific [`BundleFormat`].
+    pub fn to_write_with<W: Write>(&self, mut write: W, format: BundleFormat) -> BundleResult<()> {
+        match format {
+            BundleFormat::Ini => self.to_write(write),
+            BundleFormat::Json => {
+                let native: NativeBundleConfig = self.into();
+                Ok(serde_json::to_writer_pretty(write, &native)?)
+            }
+            BundleFormat::Toml => {
+                let native: NativeBundleConfig = self.into();
+                let text = toml::to_string_pretty(&native)?;
+                Ok(write.write_all(text.as_bytes())?)
+            }
+        }
+    }
+
     pub fn from_zipfile<P: AsRef<Path>>(path: P) -> BundleResult<Self> {
         let file = File::open(path.as_ref())?;
         let mut archive = ZipArchive::new(file)?;
         Self::from_archive(&mut archive)
     }
 
+    /// Read a bundle definition out of an archive, auto-detecting the
+    /// format from whichever of `bundle.ini`/`bundle.json`/`bundle.toml`
+    /// is present.
     pub fn from_archive<R: Read + Seek>(archive: &mut ZipArchive<R>) -> BundleResult<Self> {
-        let inifile = archive.by_name("bundle.ini")?;
-        Self::from_read(inifile)
+        for name in ["bundle.ini", "bundle.json", "bundle.toml"] {
+            match archive.by_name(name) {
+                Ok(member) => {
+                    let format = BundleFormat::from_member_name(name)
+                        .expect("bundle.{ini,json,toml} all have a recognized extension");
+                    return Self::from_read_with(member, format);
+                }
+                Err(ZipError::FileNotFound) => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(BundleError::Zip(ZipError::FileNotFound))
+    }
+
+    /// Read the base config as [`BundleConfig::from_archive`] does (from
+    /// `bundle.ini`, `bundle.json` or `bundle.toml`), then layer
+    /// `bundle.<layer>.ini` overlays
+    /// from `layer_names` on top of it in order. A layer with no matching
+    /// member in the archive is silently skipped.
+    pub fn from_archive_with_overlays<R: Read + Seek>(
+        archive: &mut ZipArchive<R>,
+        layer_names: &[&str],
+    ) -> BundleResult<Self> {
+        let mut config = Self::from_archive(archive)?;
+        for layer in layer_names {
+            let member = format!("bundle.{layer}.ini");
+            match archive.by_name(&member) {
+                Ok(file) => BundleOverride::from_read(file)?.apply(&mut config.bundle),
+                Err(ZipError::FileNotFound) => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(config)
     }
 
     pub fn to_archive<W: Write + Seek>(&self, writer: &mut ZipWriter<W>) -> BundleResult<()> {
@@ -423,6 +1599,149 @@ impl BundleConfig {
         writer.start_file("bundle.ini", options)?;
         self.to_write(writer)
     }
+
+    /// Write `bundle.ini` plus every named resource in `assets` into `writer`,
+    /// producing a complete, self-contained bundle archive.
+    ///
+    /// Fails with [`BundleError::MissingAsset`] if `encrypted_image`, `exec`,
+    /// `launcher_exec`, or the executable of any launch entry names a file
+    /// that isn't present in `assets`; this check runs before anything is
+    /// written.
+    pub fn pack_archive<W, R>(
+        &self,
+        writer: &mut ZipWriter<W>,
+        assets: &mut [(String, R)],
+    ) -> BundleResult<()>
+    where
+        W: Write + Seek,
+        R: Read,
+    {
+        let provided: HashSet<&str> = assets.iter().map(|(name, _)| name.as_str()).collect();
+        for asset in self.bundle.referenced_assets() {
+            if !provided.contains(asset) {
+                return Err(BundleError::MissingAsset(asset.to_string()));
+            }
+        }
+
+        self.to_archive(writer)?;
+
+        let options = zip::write::FileOptions::default();
+        for (name, data) in assets.iter_mut() {
+            writer.start_file(name.as_str(), options)?;
+            io::copy(data, writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pull a single named resource back out of a bundle archive.
+    pub fn extract_file<R: Read + Seek>(
+        archive: &mut ZipArchive<R>,
+        name: &str,
+    ) -> BundleResult<Vec<u8>> {
+        let mut file = archive.by_name(name)?;
+        let mut buf = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// List every member of a bundle archive, `bundle.ini` included.
+    pub fn list_files<R: Read + Seek>(archive: &ZipArchive<R>) -> Vec<String> {
+        archive.file_names().map(String::from).collect()
+    }
+
+    /// Extract this bundle's `encrypted_image`, if it has one.
+    pub fn extract_encrypted_image<R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+    ) -> BundleResult<Vec<u8>> {
+        let name = self
+            .bundle
+            .encrypted_image
+            .as_deref()
+            .ok_or_else(|| BundleError::MissingAsset("encrypted_image".to_string()))?;
+        Self::extract_file(archive, name)
+    }
+
+    /// Check every file named in the integrity manifest against the
+    /// archive, returning the files that disagree on size or digest (or are
+    /// missing outright). An empty result means the archive is intact.
+    pub fn verify_archive<R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+    ) -> BundleResult<Vec<IntegrityMismatch>> {
+        let mut mismatches = Vec::new();
+        for entry in &self.bundle.integrity {
+            if entry.digest.len() != entry.algorithm.digest_len()
+                || !entry.digest.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err(BundleError::Integrity(format!(
+                    "'{}' has a malformed {} digest",
+                    entry.path, entry.algorithm
+                )));
+            }
+
+            let mut file = match archive.by_name(&entry.path) {
+                Ok(file) => file,
+                Err(ZipError::FileNotFound) => {
+                    mismatches.push(IntegrityMismatch::Missing {
+                        path: entry.path.clone(),
+                    });
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let actual_size = file.size();
+            if actual_size != entry.size {
+                mismatches.push(IntegrityMismatch::SizeMismatch {
+                    path: entry.path.clone(),
+                    expected: entry.size,
+                    actual: actual_size,
+                });
+                continue;
+            }
+
+            let actual_digest = entry.algorithm.digest(&mut file)?;
+            if actual_digest != entry.digest {
+                mismatches.push(IntegrityMismatch::DigestMismatch {
+                    path: entry.path.clone(),
+                    expected: entry.digest.clone(),
+                    actual: actual_digest,
+                });
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Populate the integrity manifest from every file currently in
+    /// `archive` other than `bundle.ini`, using SHA-256. A `bundle.json` or
+    /// `bundle.toml` member is hashed like any other file. Since
+    /// this needs an already-packed archive to hash, the usual flow is:
+    /// [`BundleConfig::pack_archive`], reopen the result, `generate_integrity`,
+    /// then [`BundleConfig::pack_archive`] again to write the updated manifest.
+    pub fn generate_integrity<R: Read + Seek>(
+        &mut self,
+        archive: &mut ZipArchive<R>,
+    ) -> BundleResult<()> {
+        let mut entries = Vec::new();
+        for name in Self::list_files(archive) {
+            if name == "bundle.ini" {
+                continue;
+            }
+            let mut file = archive.by_name(&name)?;
+            let size = file.size();
+            let digest = HashAlgo::Sha256.digest(&mut file)?;
+            entries.push(IntegrityEntry {
+                path: name,
+                algorithm: HashAlgo::Sha256,
+                digest,
+                size,
+            });
+        }
+        self.bundle.integrity = entries;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -573,4 +1892,493 @@ EncryptedImage=bundle.img
             serde_ini::from_str(&c).expect("failed to re-deserialize test input");
         check(&conf);
     }
+
+    #[test]
+    fn test_pack_and_extract_archive() {
+        let conf = BundleConfig::builder("Gamepad".to_string(), BundleType::Game)
+            .store_id("DummyStoreID".to_string())
+            .exec("game.exe".to_string())
+            .build();
+
+        let mut zip_bytes = Vec::new();
+        {
+            let mut writer = ZipWriter::new(io::Cursor::new(&mut zip_bytes));
+            let mut assets = [("game.exe".to_string(), &b"executable bytes"[..])];
+            conf.pack_archive(&mut writer, &mut assets)
+                .expect("failed to pack archive");
+            writer.finish().expect("failed to finish archive");
+        }
+
+        let mut archive =
+            ZipArchive::new(io::Cursor::new(zip_bytes)).expect("failed to reopen archive");
+        let mut files = BundleConfig::list_files(&archive);
+        files.sort();
+        assert_eq!(
+            files,
+            vec!["bundle.ini".to_string(), "game.exe".to_string()]
+        );
+        assert_eq!(
+            BundleConfig::extract_file(&mut archive, "game.exe").expect("failed to extract file"),
+            b"executable bytes".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_pack_archive_missing_asset() {
+        let conf = BundleConfig::builder("Gamepad".to_string(), BundleType::Game)
+            .store_id("DummyStoreID".to_string())
+            .exec("game.exe".to_string())
+            .build();
+
+        let mut zip_bytes = Vec::new();
+        let mut writer = ZipWriter::new(io::Cursor::new(&mut zip_bytes));
+        let mut assets: [(String, &[u8]); 0] = [];
+        assert!(matches!(
+            conf.pack_archive(&mut writer, &mut assets),
+            Err(BundleError::MissingAsset(asset)) if asset == "game.exe"
+        ));
+    }
+
+    #[test]
+    fn test_extract_encrypted_image() {
+        let mut conf = BundleConfig::builder("Gamepad".to_string(), BundleType::Application)
+            .store_id("DummyStoreID".to_string())
+            .build();
+        conf.bundle.encrypted_image = Some("bundle.img".to_string());
+
+        let mut zip_bytes = Vec::new();
+        {
+            let mut writer = ZipWriter::new(io::Cursor::new(&mut zip_bytes));
+            let mut assets = [("bundle.img".to_string(), &b"encrypted payload"[..])];
+            conf.pack_archive(&mut writer, &mut assets)
+                .expect("failed to pack archive");
+            writer.finish().expect("failed to finish archive");
+        }
+
+        let mut archive =
+            ZipArchive::new(io::Cursor::new(zip_bytes)).expect("failed to reopen archive");
+        assert_eq!(
+            conf.extract_encrypted_image(&mut archive)
+                .expect("failed to extract encrypted image"),
+            b"encrypted payload".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_launch_entry_round_trip_with_delimiters() {
+        let entry = LaunchEntry::new(
+            Some(Platform::Windows),
+            "game.exe".to_string(),
+            vec!["--flag=a;b".to_string(), "path|with,delims".to_string()],
+        );
+        let encoded = entry.to_string();
+        let decoded: LaunchEntry = encoded.parse().expect("failed to parse launch entry");
+        assert_eq!(decoded, entry);
+    }
+
+    #[test]
+    fn test_launch_entries_list_round_trip_with_delimiters() {
+        let mut conf = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .build();
+        conf.bundle
+            .add_launch(None, "game.exe".to_string(), vec!["--flag=a;b".to_string()]);
+        conf.bundle.add_launch(
+            Some(Platform::Windows),
+            "game2.exe".to_string(),
+            vec!["x".to_string()],
+        );
+
+        let mut out = Vec::new();
+        conf.to_write(&mut out).expect("failed to serialize");
+        let reparsed =
+            BundleConfig::from_read(out.as_slice()).expect("failed to re-deserialize");
+        assert_eq!(reparsed.bundle.launch_entries, conf.bundle.launch_entries);
+    }
+
+    #[test]
+    fn test_resolve_exec_precedence() {
+        let mut conf = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .exec("legacy.exe".to_string())
+            .build();
+
+        assert_eq!(
+            conf.bundle.resolve_exec(Platform::Linux),
+            Some(LaunchEntry::new(None, "legacy.exe".to_string(), Vec::new()))
+        );
+
+        conf.bundle
+            .add_launch(None, "generic.exe".to_string(), Vec::new());
+        assert_eq!(
+            conf.bundle.resolve_exec(Platform::Linux),
+            Some(LaunchEntry::new(None, "generic.exe".to_string(), Vec::new()))
+        );
+
+        conf.bundle.add_launch(
+            Some(Platform::Linux),
+            "linux.exe".to_string(),
+            Vec::new(),
+        );
+        assert_eq!(
+            conf.bundle.resolve_exec(Platform::Linux),
+            Some(LaunchEntry::new(
+                Some(Platform::Linux),
+                "linux.exe".to_string(),
+                Vec::new()
+            ))
+        );
+        assert_eq!(
+            conf.bundle.resolve_exec(Platform::Mac),
+            Some(LaunchEntry::new(None, "generic.exe".to_string(), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn test_env_var_round_trip_with_delimiters() {
+        let var = EnvVar {
+            key: "FLAGS".to_string(),
+            value: "a;b|c,d".to_string(),
+        };
+        let encoded = var.to_string();
+        let decoded: EnvVar = encoded.parse().expect("failed to parse env var");
+        assert_eq!(decoded, var);
+    }
+
+    #[test]
+    fn test_env_var_round_trip_with_equals_in_key() {
+        let var = EnvVar {
+            key: "WINE=DEBUG".to_string(),
+            value: "1".to_string(),
+        };
+        let encoded = var.to_string();
+        let decoded: EnvVar = encoded.parse().expect("failed to parse env var");
+        assert_eq!(decoded, var);
+    }
+
+    #[test]
+    fn test_compatibility_round_trip_with_delimiters() {
+        let mut conf = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .build()
+            .bundle;
+        conf.env.push(EnvVar {
+            key: "WINEDEBUG".to_string(),
+            value: "-all;fixme-d3d".to_string(),
+        });
+        conf.env.push(EnvVar {
+            key: "DXVK_HUD".to_string(),
+            value: "1".to_string(),
+        });
+
+        let mut out = Vec::new();
+        BundleConfig { bundle: conf.clone() }
+            .to_write(&mut out)
+            .expect("failed to serialize");
+        let reparsed =
+            BundleConfig::from_read(out.as_slice()).expect("failed to re-deserialize");
+        assert_eq!(reparsed.bundle.env, conf.env);
+        assert_eq!(
+            reparsed.bundle.compatibility().environment(),
+            vec![
+                (
+                    "WINEDEBUG".to_string(),
+                    "-all;fixme-d3d".to_string()
+                ),
+                ("DXVK_HUD".to_string(), "1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_integrity_entry_round_trip_with_delimiters() {
+        let entry = IntegrityEntry {
+            path: "assets/my|weird,file;name.bin".to_string(),
+            algorithm: HashAlgo::Sha256,
+            digest: "a".repeat(64),
+            size: 42,
+        };
+        let encoded = entry.to_string();
+        let decoded: IntegrityEntry = encoded.parse().expect("failed to parse integrity entry");
+        assert_eq!(decoded, entry);
+    }
+
+    fn packed_archive_with_asset(data: &[u8]) -> Vec<u8> {
+        let conf = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .exec("game.exe".to_string())
+            .build();
+        let mut zip_bytes = Vec::new();
+        {
+            let mut writer = ZipWriter::new(io::Cursor::new(&mut zip_bytes));
+            let mut assets = [("game.exe".to_string(), data)];
+            conf.pack_archive(&mut writer, &mut assets)
+                .expect("failed to pack archive");
+            writer.finish().expect("failed to finish archive");
+        }
+        zip_bytes
+    }
+
+    #[test]
+    fn test_generate_and_verify_archive_intact() {
+        let zip_bytes = packed_archive_with_asset(b"executable bytes");
+        let mut archive =
+            ZipArchive::new(io::Cursor::new(zip_bytes)).expect("failed to reopen archive");
+
+        let mut conf = BundleConfig::from_archive(&mut archive).expect("failed to read config");
+        conf.generate_integrity(&mut archive)
+            .expect("failed to generate integrity manifest");
+        assert_eq!(conf.bundle.integrity.len(), 1);
+
+        assert_eq!(
+            conf.verify_archive(&mut archive)
+                .expect("failed to verify archive"),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_verify_archive_classifies_mismatches() {
+        let zip_bytes = packed_archive_with_asset(b"executable bytes");
+        let mut archive =
+            ZipArchive::new(io::Cursor::new(zip_bytes)).expect("failed to reopen archive");
+        let mut conf = BundleConfig::from_archive(&mut archive).expect("failed to read config");
+        conf.generate_integrity(&mut archive)
+            .expect("failed to generate integrity manifest");
+
+        conf.bundle.integrity.push(IntegrityEntry {
+            path: "missing.bin".to_string(),
+            algorithm: HashAlgo::Sha256,
+            digest: "a".repeat(64),
+            size: 1,
+        });
+        conf.bundle.integrity[0].size += 1;
+
+        let mismatches = conf
+            .verify_archive(&mut archive)
+            .expect("failed to verify archive");
+        assert!(matches!(
+            &mismatches[0],
+            IntegrityMismatch::SizeMismatch { path, .. } if path == "game.exe"
+        ));
+        assert!(matches!(
+            &mismatches[1],
+            IntegrityMismatch::Missing { path } if path == "missing.bin"
+        ));
+    }
+
+    #[test]
+    fn test_to_write_preserving_unchanged() {
+        let input = "[Bundle]\nName=Test\nType=Game\nStoreID=TestStoreID\n; a comment\nVersion=1.0\n";
+        let (conf, tokens) =
+            BundleConfig::from_read_preserving(input.as_bytes()).expect("failed to parse");
+
+        let mut out = Vec::new();
+        conf.to_write_preserving(&tokens, &mut out)
+            .expect("failed to write");
+        assert_eq!(String::from_utf8(out).unwrap(), input);
+    }
+
+    #[test]
+    fn test_to_write_preserving_clears_removed_fields() {
+        let input = "[Bundle]\nName=Test\nType=Game\nStoreID=TestStoreID\nVersion=1.0\nBackground=true\n";
+        let (mut conf, tokens) =
+            BundleConfig::from_read_preserving(input.as_bytes()).expect("failed to parse");
+
+        conf.bundle.version = None;
+        conf.bundle.background = false;
+
+        let mut out = Vec::new();
+        conf.to_write_preserving(&tokens, &mut out)
+            .expect("failed to write");
+        let out = String::from_utf8(out).unwrap();
+        assert!(!out.contains("Version="));
+        assert!(!out.contains("Background="));
+    }
+
+    #[test]
+    fn test_from_read_preserving_keeps_unknown_keys() {
+        let input =
+            "[Bundle]\nName=Test\nType=Game\nStoreID=TestStoreID\nSomeFutureKey=keepme\n";
+        let (conf, tokens) =
+            BundleConfig::from_read_preserving(input.as_bytes()).expect("failed to parse");
+
+        let mut out = Vec::new();
+        conf.to_write_preserving(&tokens, &mut out)
+            .expect("failed to write");
+        assert_eq!(String::from_utf8(out).unwrap(), input);
+    }
+
+    fn sample_config() -> BundleConfig {
+        let mut conf = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .exec("game.exe".to_string())
+            .background(true)
+            .build();
+        conf.bundle
+            .add_launch(Some(Platform::Windows), "game.exe".to_string(), vec!["-x".to_string()]);
+        conf
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let conf = sample_config();
+        let mut out = Vec::new();
+        conf.to_write_with(&mut out, BundleFormat::Json)
+            .expect("failed to serialize to JSON");
+        let reparsed = BundleConfig::from_read_with(out.as_slice(), BundleFormat::Json)
+            .expect("failed to deserialize JSON");
+        assert_eq!(reparsed.bundle.name, conf.bundle.name);
+        assert_eq!(reparsed.bundle.background, conf.bundle.background);
+        assert_eq!(reparsed.bundle.launch_entries, conf.bundle.launch_entries);
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let conf = sample_config();
+        let mut out = Vec::new();
+        conf.to_write_with(&mut out, BundleFormat::Toml)
+            .expect("failed to serialize to TOML");
+        let reparsed = BundleConfig::from_read_with(out.as_slice(), BundleFormat::Toml)
+            .expect("failed to deserialize TOML");
+        assert_eq!(reparsed.bundle.name, conf.bundle.name);
+        assert_eq!(reparsed.bundle.background, conf.bundle.background);
+        assert_eq!(reparsed.bundle.launch_entries, conf.bundle.launch_entries);
+    }
+
+    #[test]
+    fn test_bundle_format_from_member_name() {
+        assert_eq!(
+            BundleFormat::from_member_name("bundle.ini"),
+            Some(BundleFormat::Ini)
+        );
+        assert_eq!(
+            BundleFormat::from_member_name("bundle.json"),
+            Some(BundleFormat::Json)
+        );
+        assert_eq!(
+            BundleFormat::from_member_name("bundle.toml"),
+            Some(BundleFormat::Toml)
+        );
+        assert_eq!(BundleFormat::from_member_name("bundle.cfg"), None);
+    }
+
+    #[test]
+    fn test_bundle_override_apply_scalars_and_replace() {
+        let mut base = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .exec("game.exe".to_string())
+            .winetricks(vec!["vcrun2019".to_string()])
+            .build()
+            .bundle;
+
+        let over = BundleOverride {
+            version: Some("1.1".to_string()),
+            winetricks: Some(vec!["dotnet48".to_string()]),
+            ..Default::default()
+        };
+        over.apply(&mut base);
+
+        assert_eq!(base.version, Some("1.1".to_string()));
+        assert_eq!(base.winetricks, vec!["dotnet48".to_string()]);
+        assert_eq!(base.exec, Some("game.exe".to_string()));
+    }
+
+    #[test]
+    fn test_bundle_override_apply_append_policy() {
+        let mut base = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .exec("game.exe".to_string())
+            .winetricks(vec!["vcrun2019".to_string()])
+            .build()
+            .bundle;
+
+        let over = BundleOverride {
+            winetricks: Some(vec!["dotnet48".to_string()]),
+            winetricks_policy: ListMergePolicy::Append,
+            ..Default::default()
+        };
+        over.apply(&mut base);
+
+        assert_eq!(
+            base.winetricks,
+            vec!["vcrun2019".to_string(), "dotnet48".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_bundle_override_apply_launch_entries_and_integrity() {
+        let mut base = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .exec("game.exe".to_string())
+            .build()
+            .bundle;
+        base.add_launch(Some(Platform::Linux), "game".to_string(), Vec::new());
+        base.integrity.push(IntegrityEntry {
+            path: "game".to_string(),
+            algorithm: HashAlgo::Sha256,
+            digest: "a".repeat(64),
+            size: 1,
+        });
+
+        let over = BundleOverride {
+            launch_entries: Some(vec![LaunchEntry::new(
+                Some(Platform::Windows),
+                "game.exe".to_string(),
+                vec!["-windowed".to_string()],
+            )]),
+            integrity: Some(vec![IntegrityEntry {
+                path: "game.exe".to_string(),
+                algorithm: HashAlgo::Sha256,
+                digest: "b".repeat(64),
+                size: 2,
+            }]),
+            ..Default::default()
+        };
+        over.apply(&mut base);
+
+        assert_eq!(
+            base.launch_entries,
+            vec![LaunchEntry::new(
+                Some(Platform::Windows),
+                "game.exe".to_string(),
+                vec!["-windowed".to_string()],
+            )]
+        );
+        assert_eq!(
+            base.integrity,
+            vec![IntegrityEntry {
+                path: "game.exe".to_string(),
+                algorithm: HashAlgo::Sha256,
+                digest: "b".repeat(64),
+                size: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_from_archive_with_overlays() {
+        let conf = BundleConfig::builder("Test".to_string(), BundleType::Game)
+            .store_id("TestStoreID".to_string())
+            .exec("game.exe".to_string())
+            .build();
+
+        let mut zip_bytes = Vec::new();
+        {
+            let mut writer = ZipWriter::new(io::Cursor::new(&mut zip_bytes));
+            let options = zip::write::FileOptions::default();
+            writer.start_file("bundle.ini", options).unwrap();
+            conf.to_write(&mut writer).unwrap();
+            writer.start_file("bundle.beta.ini", options).unwrap();
+            write!(writer, "[Bundle]\nVersion=beta-42\n").unwrap();
+            writer.finish().expect("failed to finish archive");
+        }
+
+        let mut archive =
+            ZipArchive::new(io::Cursor::new(zip_bytes)).expect("failed to reopen archive");
+        let layered = BundleConfig::from_archive_with_overlays(&mut archive, &["beta", "missing"])
+            .expect("failed to read layered config");
+        assert_eq!(layered.bundle.version, Some("beta-42".to_string()));
+        assert_eq!(layered.bundle.exec, Some("game.exe".to_string()));
+    }
 }